@@ -0,0 +1,39 @@
+//! Enumeration backends: different ways of discovering the USB TTYs currently
+//! plugged in. Callers pick one with a `BackendKind` and get back the same
+//! `Vec<PresentTty>` regardless of which was used.
+
+mod udev;
+mod rusb_backend;
+
+use crate::tty::PresentTty;
+
+/// Which enumeration strategy to use for `available_ttys`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackendKind {
+    /// Shell out to `udevadm` (the original, and still the default, backend)
+    Udev,
+    /// Talk to libusb directly via the `rusb` crate
+    Rusb,
+}
+
+impl BackendKind {
+    pub fn from_str(raw: &str) -> Result<BackendKind, String> {
+        match raw {
+            "udev" => Ok(BackendKind::Udev),
+            "rusb" => Ok(BackendKind::Rusb),
+            other => Err(format!("Unknown backend '{}'; expected 'udev' or 'rusb'", other)),
+        }
+    }
+}
+
+/// A source of information about the USB TTYs currently connected
+pub trait TtyBackend {
+    fn available_ttys(&self) -> Vec<PresentTty>;
+}
+
+pub fn backend(kind: BackendKind) -> Box<dyn TtyBackend> {
+    match kind {
+        BackendKind::Udev => Box::new(udev::UdevBackend),
+        BackendKind::Rusb => Box::new(rusb_backend::RusbBackend),
+    }
+}
@@ -0,0 +1,104 @@
+//! Enumeration backend that reads USB descriptors directly via libusb (through
+//! the `rusb` crate) instead of shelling out to `udevadm`, as lUSB-cli does.
+//! Since libusb doesn't know about `/dev/ttyUSB*` nodes, each device found
+//! this way is correlated to its tty node by matching sysfs' busnum/devnum
+//! against the bus number and address libusb reports.
+
+use std::fs;
+use std::path::Path;
+
+use glob::glob;
+use rusb::{Context, Device, UsbContext};
+
+use super::TtyBackend;
+use crate::tty::{PresentTty, Tty};
+
+pub struct RusbBackend;
+
+impl TtyBackend for RusbBackend {
+    fn available_ttys(&self) -> Vec<PresentTty> {
+        let context = match Context::new() {
+            Ok(context) => context,
+            Err(error) => {
+                eprintln!("Failed to initialize libusb: {}", error);
+                return Vec::new();
+            }
+        };
+
+        let devices = match context.devices() {
+            Ok(devices) => devices,
+            Err(error) => {
+                eprintln!("Failed to enumerate USB devices: {}", error);
+                return Vec::new();
+            }
+        };
+
+        devices.iter().filter_map(|device| read_rusb_info(&device)).collect()
+    }
+}
+
+fn read_rusb_info<T: UsbContext>(device: &Device<T>) -> Option<PresentTty> {
+    let descriptor = device.device_descriptor().ok()?;
+    let device_path = find_tty_node(device.bus_number(), device.address())?;
+
+    let handle = device.open().ok();
+    let manufacturer = handle.as_ref()
+        .and_then(|h| h.read_manufacturer_string_ascii(&descriptor).ok());
+    let model = handle.as_ref()
+        .and_then(|h| h.read_product_string_ascii(&descriptor).ok());
+    let serial = handle.as_ref()
+        .and_then(|h| h.read_serial_number_string_ascii(&descriptor).ok());
+
+    Some(PresentTty {
+        tty: Tty {
+            manufacturer,
+            model,
+            serial,
+            vendor_id: Some(descriptor.vendor_id()),
+            product_id: Some(descriptor.product_id()),
+        },
+        device: device_path,
+    })
+}
+
+/// Finds the `/dev/ttyUSB*` or `/dev/ttyACM*` node, if any, backed by the USB device at
+/// `bus_number`/`address`, by walking up each candidate's sysfs hierarchy looking for the
+/// `busnum`/`devnum` files that the kernel publishes for the owning USB device.
+fn find_tty_node(bus_number: u8, address: u8) -> Option<String> {
+    for candidate in glob("/sys/class/tty/*/device").expect("Failed to read glob pattern") {
+        let sys_path = match candidate {
+            Ok(path) => path,
+            Err(..) => continue,
+        };
+
+        let real_path = match fs::canonicalize(&sys_path) {
+            Ok(path) => path,
+            Err(..) => continue,
+        };
+
+        if sysfs_identifies(&real_path, bus_number, address) {
+            if let Some(tty_name) = sys_path.parent().and_then(|p| p.file_name()) {
+                return Some(format!("/dev/{}", tty_name.to_string_lossy()));
+            }
+        }
+    }
+
+    None
+}
+
+fn sysfs_identifies(device_dir: &Path, bus_number: u8, address: u8) -> bool {
+    for ancestor in device_dir.ancestors() {
+        let busnum = read_sysfs_u8(&ancestor.join("busnum"));
+        let devnum = read_sysfs_u8(&ancestor.join("devnum"));
+
+        if busnum == Some(bus_number) && devnum == Some(address) {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn read_sysfs_u8(path: &Path) -> Option<u8> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
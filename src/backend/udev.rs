@@ -0,0 +1,102 @@
+//! The original enumeration backend: shells out to `udevadm` and globs sysfs.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::process::Command;
+
+use glob::glob;
+use regex::{Captures, Regex};
+
+use super::TtyBackend;
+use crate::tty::{PresentTty, Tty};
+
+pub struct UdevBackend;
+
+impl TtyBackend for UdevBackend {
+    fn available_ttys(&self) -> Vec<PresentTty> {
+        available_ttys()
+    }
+}
+
+/// Converts strings with embedded hex literals like "hello\x20world" to "hello world"
+fn udevadm_decode<'a>(raw: &'a str) -> Cow<'a, str> {
+    lazy_static! {
+        static ref ESC_REGEX: Regex = Regex::new(r"\\x([[:xdigit:]]{2})")
+            .expect("error parsing regex");
+    }
+    ESC_REGEX.replace_all(raw, |caps: &Captures| {
+        match u8::from_str_radix(&caps[1], 16) {
+            Ok(val) => char::from(val),
+            Err(..) => '?',
+        }.to_string() // Replacement character to string
+    })
+}
+
+fn read_usb_info(dev: &PathBuf) -> Option<PresentTty> {
+    let raw_info = Command::new("udevadm")
+        .arg("info").arg("-q").arg("property").arg("--export").arg("-p")
+        .arg(dev)
+        .output()
+        .expect("Failed to execute udevadm");
+
+    let mut fields = HashMap::<String, String>::new();
+
+    for line in raw_info.stdout.lines() {
+        let line = line.expect("Couldn't split lines from udevadm output!?");
+        lazy_static! {
+            static ref UDEV_REGEX: Regex = Regex::new(r"(\S+)='(\S+)'")
+                .expect("error parsing regex");
+        }
+        if let Some(var_value) = UDEV_REGEX.captures(&line) {
+            fields.insert(var_value[1].to_string(), var_value[2].to_string());
+        }
+    }
+
+    // Ignore anything except USB things
+    if fields.get("ID_BUS") != Some(&String::from("usb")) {
+        return None;
+    }
+
+    // if field key in fields has Some value run udevadm_decode() on the value and return result
+    let extract_field = |field: &str| {
+        fields.get(field).map(|raw| udevadm_decode(raw).into_owned())
+    };
+
+    let extract_hex_field = |field: &str| {
+        fields.get(field).and_then(|raw| u16::from_str_radix(raw, 16).ok())
+    };
+
+    extract_field("DEVNAME").map(|devname| PresentTty {
+        tty: Tty {
+            manufacturer: extract_field("ID_VENDOR_ENC"),
+            model:        extract_field("ID_MODEL_ENC"),
+            serial:       extract_field("ID_SERIAL_SHORT"),
+            vendor_id:    extract_hex_field("ID_VENDOR_ID"),
+            product_id:   extract_hex_field("ID_MODEL_ID"),
+        },
+        device: devname,
+    })
+}
+
+// TODO Handle devices where there are multiple dev entries for the same device
+fn available_ttys() -> Vec<PresentTty> {
+    // Generate a list of device handles to inspect - https://stackoverflow.com/a/9914339
+    let mut devs = Vec::new();
+    for path in glob("/sys/class/tty/*/device/driver").expect("Failed to read glob pattern").flatten() {
+        // Turn /sys/class/tty/ttyWhatever/device/driver in to /sys/class/tty/ttyWhatever
+        if let Some(devname) = path.ancestors().nth(2) {
+            devs.push(devname.to_path_buf());
+        }
+    }
+
+    let mut ttys = Vec::new();
+    for dev in devs {
+        if let Some(tty) = read_usb_info(&dev) {
+            ttys.push(tty);
+        }
+    }
+
+    ttys
+}
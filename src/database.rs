@@ -0,0 +1,157 @@
+//! Bundled VID/PID lookup table for common USB-serial chips and dev boards, in the spirit of
+//! dmrconfig's radio table. Used to annotate unrecognized devices in `list`, and to suggest a
+//! friendly name for `add` when the user doesn't give one.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct RawEntry {
+    vendor_id: String,
+    product_id: String,
+    description: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawDatabase {
+    #[serde(default)]
+    devices: Vec<RawEntry>,
+}
+
+lazy_static! {
+    static ref BUNDLED: HashMap<(u16, u16), String> = parse(include_str!("database.toml"))
+        .expect("Bundled device database failed to parse");
+}
+
+fn parse(raw: &str) -> Result<HashMap<(u16, u16), String>, String> {
+    let raw_database: RawDatabase = toml::from_str(raw)
+        .map_err(|error| format!("Error parsing device database: {}", error))?;
+
+    let mut entries = HashMap::new();
+    for entry in raw_database.devices {
+        let vendor_id = u16::from_str_radix(&entry.vendor_id, 16)
+            .map_err(|error| format!("Invalid vendor_id '{}': {}", entry.vendor_id, error))?;
+        let product_id = u16::from_str_radix(&entry.product_id, 16)
+            .map_err(|error| format!("Invalid product_id '{}': {}", entry.product_id, error))?;
+
+        entries.insert((vendor_id, product_id), entry.description);
+    }
+
+    Ok(entries)
+}
+
+/// A VID/PID lookup table: the bundled set, plus whatever the user's `--database` override adds
+/// or replaces.
+pub struct Database {
+    entries: HashMap<(u16, u16), String>,
+}
+
+impl Database {
+    pub fn load(override_path: Option<&Path>) -> Result<Database, String> {
+        let mut entries = BUNDLED.clone();
+
+        if let Some(path) = override_path {
+            let raw = fs::read_to_string(path)
+                .map_err(|error| format!("Failed to read device database {:?}: {}", path, error))?;
+
+            for (key, description) in parse(&raw)? {
+                entries.insert(key, description);
+            }
+        }
+
+        Ok(Database { entries })
+    }
+
+    pub fn describe(&self, vendor_id: u16, product_id: u16) -> Option<&str> {
+        self.entries.get(&(vendor_id, product_id)).map(|description| description.as_str())
+    }
+
+    /// Suggests a friendly name for a device, from its recognized description and serial number.
+    pub fn suggest_name(&self, vendor_id: u16, product_id: u16, serial: Option<&str>) -> Option<String> {
+        let description = self.describe(vendor_id, product_id)?;
+        let slug = slugify(description);
+
+        Some(match serial {
+            Some(serial) => format!("{}_{}", slug, slugify(serial)),
+            None => slug,
+        })
+    }
+}
+
+/// Turns free text into something that satisfies the friendly-name character restrictions
+fn slugify(raw: &str) -> String {
+    let mut slug = String::new();
+    let mut last_was_sep = true; // Avoids a leading separator
+
+    for ch in raw.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+            last_was_sep = false;
+        } else if !last_was_sep {
+            slug.push('_');
+            last_was_sep = true;
+        }
+    }
+
+    slug.trim_end_matches('_').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_lowercases_and_collapses_separators() {
+        assert_eq!(slugify("FTDI FT232R USB UART"), "ftdi_ft232r_usb_uart");
+        assert_eq!(slugify("CP2102N USB to UART Bridge Controller"), "cp2102n_usb_to_uart_bridge_controller");
+    }
+
+    #[test]
+    fn slugify_collapses_runs_of_punctuation_into_one_separator() {
+        // Two different-looking descriptions collide on the same slug once punctuation and case
+        // are normalized away; suggest_unused_name's numeric suffixing is what disambiguates that.
+        assert_eq!(slugify("Arduino / Genuino Uno"), slugify("Arduino--Genuino--Uno"));
+        assert_eq!(slugify("Arduino / Genuino Uno"), "arduino_genuino_uno");
+    }
+
+    #[test]
+    fn slugify_trims_leading_and_trailing_separators() {
+        assert_eq!(slugify("  --Odd Spacing-- "), "odd_spacing");
+    }
+
+    #[test]
+    fn slugify_empty_input_is_empty() {
+        assert_eq!(slugify(""), "");
+        assert_eq!(slugify("***"), "");
+    }
+
+    #[test]
+    fn suggest_name_combines_description_and_serial() {
+        let mut entries = HashMap::new();
+        entries.insert((0x0403u16, 0x6001u16), "FTDI FT232R USB UART".to_string());
+        let database = Database { entries };
+
+        assert_eq!(
+            database.suggest_name(0x0403, 0x6001, Some("AB123")),
+            Some("ftdi_ft232r_usb_uart_ab123".to_string())
+        );
+    }
+
+    #[test]
+    fn suggest_name_without_serial_is_just_the_slug() {
+        let mut entries = HashMap::new();
+        entries.insert((0x0403u16, 0x6001u16), "FTDI FT232R USB UART".to_string());
+        let database = Database { entries };
+
+        assert_eq!(database.suggest_name(0x0403, 0x6001, None), Some("ftdi_ft232r_usb_uart".to_string()));
+    }
+
+    #[test]
+    fn suggest_name_unknown_vid_pid_is_none() {
+        let database = Database { entries: HashMap::new() };
+        assert_eq!(database.suggest_name(0x0403, 0x6001, Some("AB123")), None);
+    }
+}
@@ -0,0 +1,88 @@
+//! `disable`/`enable` subcommands: unbind or rebind a device's kernel driver via sysfs, porting
+//! lUSB-cli's enable/disable verbs. Lets a user quiet a misbehaving adapter, or force
+//! re-enumeration, without physically unplugging it.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Serialize, Deserialize};
+
+use crate::backend::TtyBackend;
+use crate::Configuration;
+
+/// The USB bus id and kernel driver a device was bound to when it was `disable`d, cached so
+/// `enable` can rebind it later without needing the device to still have a live tty node:
+/// unbinding its driver is exactly what makes it vanish from `/sys/class/tty/*` until rebound.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DisabledDevice {
+    bus_id: String,
+    driver: String,
+}
+
+pub fn disable(name: &str, config: &mut Configuration, backend: &dyn TtyBackend) -> Result<(), String> {
+    let device = crate::resolve_device(name, config, backend)?;
+    let device_node = device.trim_start_matches("/dev/");
+
+    let (bus_id, driver) = resolve_bus_id_and_driver(device_node)?;
+
+    write_control(&driver, "unbind", &bus_id)?;
+
+    config.disabled.insert(name.to_string(), DisabledDevice { bus_id, driver });
+    println!("{} was disabled successfully!", name);
+    Ok(())
+}
+
+pub fn enable(name: &str, config: &mut Configuration) -> Result<(), String> {
+    let disabled = config.disabled.remove(name)
+        .ok_or_else(|| format!("{} isn't currently disabled.", name))?;
+
+    if let Err(error) = write_control(&disabled.driver, "bind", &disabled.bus_id) {
+        // Still disabled; put the cached bus id/driver back so another `enable` can retry.
+        config.disabled.insert(name.to_string(), disabled);
+        return Err(error);
+    }
+
+    println!("{} was enabled successfully!", name);
+    Ok(())
+}
+
+/// Writes `bus_id` to `/sys/bus/usb/drivers/<driver>/<action>`, translating the failure modes a
+/// user is likely to hit into a friendly `Result`.
+fn write_control(driver: &str, action: &str, bus_id: &str) -> Result<(), String> {
+    let control_path = format!("/sys/bus/usb/drivers/{}/{}", driver, action);
+
+    match fs::write(&control_path, bus_id.as_bytes()) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+            Err(format!("Permission denied writing to {}; this operation needs root.", control_path))
+        }
+        Err(error) => Err(format!("Failed to {} {} via {}: {}", action, bus_id, control_path, error)),
+    }
+}
+
+/// Finds the USB bus id (e.g. "3-1:1.0") and kernel driver name currently bound to the tty
+/// device named `device_node` (e.g. "ttyUSB0"), by resolving sysfs symlinks.
+fn resolve_bus_id_and_driver(device_node: &str) -> Result<(String, String), String> {
+    let device_dir = PathBuf::from(format!("/sys/class/tty/{}/device", device_node));
+
+    let device_path = fs::canonicalize(&device_dir)
+        .map_err(|error| format!("Couldn't resolve sysfs device for {}: {}", device_node, error))?;
+
+    // A ttyACM's device is the USB interface itself, but a usb-serial ttyUSB's device is a port
+    // directory beneath it, so walk up to the first component that looks like "3-1:1.0"
+    let interface_path = device_path.ancestors()
+        .find(|path| path.file_name().map_or(false, |name| name.to_string_lossy().contains(':')))
+        .ok_or_else(|| format!("Couldn't find the USB interface for {}", device_node))?;
+    let bus_id = interface_path.file_name()
+        .ok_or_else(|| format!("Couldn't determine bus id for {}", device_node))?
+        .to_string_lossy().to_string();
+
+    let driver_path = fs::canonicalize(interface_path.join("driver"))
+        .map_err(|error| format!("Couldn't resolve driver for {}: {}", device_node, error))?;
+    let driver = driver_path.file_name()
+        .ok_or_else(|| format!("Couldn't determine driver name for {}", device_node))?
+        .to_string_lossy().to_string();
+
+    Ok((bus_id, driver))
+}
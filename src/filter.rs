@@ -0,0 +1,183 @@
+//! Glob-pattern device filters, and the `match` subcommand that tests them against what's
+//! currently connected. Mirrors the answer-file filter testing in proxmox-autoinst-helper and
+//! the `*` serial wildcard in microdeck's config.
+
+use std::collections::HashMap;
+
+use glob::Pattern;
+use serde::{Serialize, Deserialize};
+
+use crate::backend::TtyBackend;
+use crate::tty::Tty;
+use crate::Configuration;
+
+/// A device filter: every *specified* field must glob-match the corresponding field on a
+/// present device; unspecified fields are wildcards.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TtyFilter {
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+}
+
+impl TtyFilter {
+    pub fn matches(&self, tty: &Tty) -> bool {
+        field_matches(&self.manufacturer, &tty.manufacturer)
+            && field_matches(&self.model, &tty.model)
+            && field_matches(&self.serial, &tty.serial)
+    }
+}
+
+/// Implements the `filter add` subcommand: defines (or redefines) a named filter in `config`.
+pub fn add(
+    name: &str,
+    manufacturer: Option<&str>,
+    model: Option<&str>,
+    serial: Option<&str>,
+    config: &mut Configuration,
+) -> Result<(), String> {
+    if manufacturer.is_none() && model.is_none() && serial.is_none() {
+        return Err("At least one of --manufacturer, --model, or --serial is required.".to_string());
+    }
+
+    config.filters.insert(name.to_string(), TtyFilter {
+        manufacturer: manufacturer.map(str::to_string),
+        model: model.map(str::to_string),
+        serial: serial.map(str::to_string),
+    });
+
+    Ok(())
+}
+
+fn field_matches(pattern: &Option<String>, value: &Option<String>) -> bool {
+    let pattern = match pattern {
+        Some(pattern) => pattern,
+        None => return true, // Unspecified fields are wildcards
+    };
+
+    let value = match value {
+        Some(value) => value,
+        None => return false,
+    };
+
+    match Pattern::new(pattern) {
+        Ok(compiled) => compiled.matches(value),
+        Err(error) => {
+            eprintln!("Invalid glob pattern '{}': {}", pattern, error);
+            false
+        }
+    }
+}
+
+/// Implements the `match` subcommand: for each connected device, prints which named filters it
+/// satisfies, and flags filters that match several devices or devices matched by several filters.
+pub fn run(filters: &HashMap<String, TtyFilter>, backend: &dyn TtyBackend) -> Result<(), String> {
+    let present = backend.available_ttys();
+
+    if present.is_empty() {
+        println!("No USB TTYs present.");
+        return Ok(());
+    }
+
+    let mut devices_by_filter: HashMap<&String, Vec<&str>> = HashMap::new();
+
+    for device in &present {
+        let matching: Vec<&String> = filters.iter()
+            .filter(|(_, filter)| filter.matches(&device.tty))
+            .map(|(name, _)| name)
+            .collect();
+
+        for name in &matching {
+            devices_by_filter.entry(name).or_default().push(&device.device);
+        }
+
+        let summary = if matching.is_empty() {
+            "(no match)".to_string()
+        } else {
+            matching.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", ")
+        };
+
+        let ambiguous = if matching.len() > 1 { "  [AMBIGUOUS: matched by multiple filters]" } else { "" };
+
+        println!("{}: {}{}", device.device, summary, ambiguous);
+    }
+
+    for (name, devices) in &devices_by_filter {
+        if devices.len() > 1 {
+            println!("Warning: filter '{}' matches multiple devices: {}", name, devices.join(", "));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tty(manufacturer: &str, model: &str, serial: &str) -> Tty {
+        Tty {
+            manufacturer: Some(manufacturer.to_string()),
+            model: Some(model.to_string()),
+            serial: Some(serial.to_string()),
+            vendor_id: None,
+            product_id: None,
+        }
+    }
+
+    #[test]
+    fn empty_filter_matches_anything() {
+        let filter = TtyFilter::default();
+        assert!(filter.matches(&tty("FTDI", "FT232R", "AB123")));
+    }
+
+    #[test]
+    fn unspecified_field_is_a_wildcard() {
+        let filter = TtyFilter {
+            manufacturer: Some("FTDI".to_string()),
+            model: None,
+            serial: None,
+        };
+
+        assert!(filter.matches(&tty("FTDI", "FT232R", "AB123")));
+        assert!(filter.matches(&tty("FTDI", "anything else", "anything else")));
+        assert!(!filter.matches(&tty("Other", "FT232R", "AB123")));
+    }
+
+    #[test]
+    fn specified_field_glob_matches() {
+        let filter = TtyFilter {
+            manufacturer: None,
+            model: None,
+            serial: Some("AB*".to_string()),
+        };
+
+        assert!(filter.matches(&tty("FTDI", "FT232R", "AB123")));
+        assert!(!filter.matches(&tty("FTDI", "FT232R", "CD123")));
+    }
+
+    #[test]
+    fn specified_field_requires_value_to_be_present() {
+        let filter = TtyFilter {
+            manufacturer: Some("*".to_string()),
+            model: None,
+            serial: None,
+        };
+
+        let mut incomplete = tty("FTDI", "FT232R", "AB123");
+        incomplete.manufacturer = None;
+
+        assert!(!filter.matches(&incomplete));
+    }
+
+    #[test]
+    fn invalid_glob_pattern_does_not_match() {
+        let filter = TtyFilter {
+            manufacturer: Some("[".to_string()),
+            model: None,
+            serial: None,
+        };
+
+        assert!(!filter.matches(&tty("FTDI", "FT232R", "AB123")));
+    }
+}
@@ -0,0 +1,248 @@
+//! Shared representation for `list` output, so the table and JSON renderers show exactly the
+//! same information about each device.
+
+use std::cmp;
+use std::io::IsTerminal;
+
+use serde::Serialize;
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+use crate::database::Database;
+use crate::tty::{pon, Tty};
+
+/// How a device relates to the configuration, corresponding to the green/black/yellow/red
+/// colours the table view has always used.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtyStatus {
+    /// Connected, and matches a named configuration entry
+    Matched,
+    /// Connected, but doesn't match any named configuration entry
+    Unknown,
+    /// Connected and unmatched, but missing some of manufacturer/model/serial
+    Incomplete,
+    /// A named configuration entry that isn't currently connected
+    Missing,
+}
+
+impl TtyStatus {
+    fn colour(&self) -> Color {
+        match self {
+            TtyStatus::Matched => Color::Green,
+            TtyStatus::Unknown => Color::Black, // Sentinel for no colour
+            TtyStatus::Incomplete => Color::Yellow,
+            TtyStatus::Missing => Color::Red,
+        }
+    }
+}
+
+/// One row of `list` output
+#[derive(Debug, Serialize)]
+pub struct ListRow {
+    pub name: Option<String>,
+    pub device: Option<String>,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+    pub status: TtyStatus,
+
+    /// The bundled database's description of the device's chip/board, if recognized
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recognized_as: Option<String>,
+}
+
+impl ListRow {
+    pub fn matched(name: String, device: String, tty: &Tty) -> ListRow {
+        ListRow {
+            name: Some(name),
+            device: Some(device),
+            manufacturer: tty.manufacturer.clone(),
+            model: tty.model.clone(),
+            serial: tty.serial.clone(),
+            status: TtyStatus::Matched,
+            recognized_as: None,
+        }
+    }
+
+    pub fn unmatched(device: String, tty: &Tty, database: &Database) -> ListRow {
+        let status = if tty.manufacturer.is_none() || tty.model.is_none() || tty.serial.is_none() {
+            TtyStatus::Incomplete
+        } else {
+            TtyStatus::Unknown
+        };
+
+        let recognized_as = match (tty.vendor_id, tty.product_id) {
+            (Some(vendor_id), Some(product_id)) => database.describe(vendor_id, product_id)
+                .map(|description| description.to_string()),
+            _ => None,
+        };
+
+        ListRow {
+            name: None,
+            device: Some(device),
+            manufacturer: tty.manufacturer.clone(),
+            model: tty.model.clone(),
+            serial: tty.serial.clone(),
+            status,
+            recognized_as,
+        }
+    }
+
+    pub fn missing(name: String, tty: &Tty) -> ListRow {
+        ListRow {
+            name: Some(name),
+            device: None,
+            manufacturer: tty.manufacturer.clone(),
+            model: tty.model.clone(),
+            serial: tty.serial.clone(),
+            status: TtyStatus::Missing,
+            recognized_as: None,
+        }
+    }
+}
+
+pub fn print_table(rows: &[ListRow]) {
+    const NUM_COLS: usize = 5;
+
+    let text_rows: Vec<[String; NUM_COLS]> = rows.iter().map(|row| [
+        row.name.clone().unwrap_or_default(),
+        row.device.clone().unwrap_or_else(|| "(missing)".to_string()),
+        pon(&row.manufacturer),
+        pon(&row.model),
+        pon(&row.serial),
+    ]).collect();
+
+    let mut widths = [0usize; NUM_COLS];
+    for row in &text_rows {
+        for (column_index, field) in row.iter().enumerate() {
+            widths[column_index] = cmp::max(field.len(), widths[column_index]);
+        }
+    }
+
+    // Colour is disabled when stdout isn't a terminal; otherwise ColorChoice::Auto also honours
+    // TERM and NO_COLOR
+    let colour_choice = if std::io::stdout().is_terminal() { ColorChoice::Auto } else { ColorChoice::Never };
+    let mut stdout = StandardStream::stdout(colour_choice);
+    for (row, text) in rows.iter().zip(text_rows.iter()) {
+        let colour = row.status.colour();
+        if colour == Color::Black {
+            stdout.set_color(&ColorSpec::new()).expect("Colour change failed");
+        } else {
+            stdout.set_color(ColorSpec::new().set_fg(Some(colour)))
+                .expect("Colour change failed");
+        }
+
+        for (column_index, field) in text.iter().enumerate() {
+            if widths[column_index] > 0 {
+                print!("{:1$}", field, widths[column_index] + 2);
+            }
+        }
+
+        if let Some(recognized_as) = &row.recognized_as {
+            print!("(recognized as: {})", recognized_as);
+        }
+
+        println!();
+    }
+
+    if rows.is_empty() {
+        println!("No USB TTYs present.");
+    }
+}
+
+pub fn print_json(rows: &[ListRow]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(rows)
+        .map_err(|error| format!("Failed to serialize rows: {}", error))?;
+    println!("{}", json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complete_tty() -> Tty {
+        Tty {
+            manufacturer: Some("FTDI".to_string()),
+            model: Some("FT232R".to_string()),
+            serial: Some("AB123".to_string()),
+            vendor_id: Some(0x0403),
+            product_id: Some(0x6001),
+        }
+    }
+
+    #[test]
+    fn matched_row_carries_name_device_and_matched_status() {
+        let row = ListRow::matched("my_board".to_string(), "/dev/ttyUSB0".to_string(), &complete_tty());
+
+        assert_eq!(row.name, Some("my_board".to_string()));
+        assert_eq!(row.device, Some("/dev/ttyUSB0".to_string()));
+        assert!(matches!(row.status, TtyStatus::Matched));
+        assert_eq!(row.recognized_as, None);
+    }
+
+    #[test]
+    fn unmatched_row_with_all_fields_is_unknown() {
+        let database = Database::load(None).expect("bundled database should load");
+        let row = ListRow::unmatched("/dev/ttyUSB0".to_string(), &complete_tty(), &database);
+
+        assert!(row.name.is_none());
+        assert!(matches!(row.status, TtyStatus::Unknown));
+    }
+
+    #[test]
+    fn unmatched_row_missing_a_field_is_incomplete() {
+        let database = Database::load(None).expect("bundled database should load");
+        let mut tty = complete_tty();
+        tty.serial = None;
+
+        let row = ListRow::unmatched("/dev/ttyUSB0".to_string(), &tty, &database);
+
+        assert!(matches!(row.status, TtyStatus::Incomplete));
+    }
+
+    #[test]
+    fn unmatched_row_recognizes_bundled_vid_pid() {
+        let database = Database::load(None).expect("bundled database should load");
+        let row = ListRow::unmatched("/dev/ttyUSB0".to_string(), &complete_tty(), &database);
+
+        assert_eq!(row.recognized_as.as_deref(), Some("FTDI FT232 USB-Serial"));
+    }
+
+    #[test]
+    fn unmatched_row_without_vid_pid_is_not_recognized() {
+        let database = Database::load(None).expect("bundled database should load");
+        let mut tty = complete_tty();
+        tty.vendor_id = None;
+        tty.product_id = None;
+
+        let row = ListRow::unmatched("/dev/ttyUSB0".to_string(), &tty, &database);
+
+        assert_eq!(row.recognized_as, None);
+    }
+
+    #[test]
+    fn missing_row_has_no_device_and_missing_status() {
+        let row = ListRow::missing("my_board".to_string(), &complete_tty());
+
+        assert_eq!(row.name, Some("my_board".to_string()));
+        assert_eq!(row.device, None);
+        assert!(matches!(row.status, TtyStatus::Missing));
+    }
+
+    #[test]
+    fn status_serializes_to_lowercase_json() {
+        assert_eq!(serde_json::to_string(&TtyStatus::Matched).unwrap(), "\"matched\"");
+        assert_eq!(serde_json::to_string(&TtyStatus::Unknown).unwrap(), "\"unknown\"");
+        assert_eq!(serde_json::to_string(&TtyStatus::Incomplete).unwrap(), "\"incomplete\"");
+        assert_eq!(serde_json::to_string(&TtyStatus::Missing).unwrap(), "\"missing\"");
+    }
+
+    #[test]
+    fn missing_row_omits_recognized_as_from_json() {
+        let row = ListRow::missing("my_board".to_string(), &complete_tty());
+        let json = serde_json::to_string(&row).unwrap();
+
+        assert!(!json.contains("recognized_as"));
+    }
+}
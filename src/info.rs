@@ -0,0 +1,70 @@
+//! `info` subcommand: dumps every udev property of a given `/dev` node, or all present TTY
+//! nodes, so users can discover exactly which strings are available to filter on.
+
+use std::io::BufRead;
+use std::process::Command;
+
+use glob::glob;
+use regex::Regex;
+
+pub fn run(device: Option<&str>) -> Result<(), String> {
+    let targets = match device {
+        Some(device) => vec![device.to_string()],
+        None => all_tty_devices(),
+    };
+
+    if targets.is_empty() {
+        println!("No USB TTYs present.");
+        return Ok(());
+    }
+
+    for (index, device) in targets.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("{}:", device);
+
+        for (key, value) in dump_properties(device)? {
+            println!("  {}={}", key, value);
+        }
+    }
+
+    Ok(())
+}
+
+fn all_tty_devices() -> Vec<String> {
+    let mut devices = Vec::new();
+    for path in glob("/sys/class/tty/*/device/driver").expect("Failed to read glob pattern").flatten() {
+        if let Some(devname) = path.ancestors().nth(2).and_then(|p| p.file_name()) {
+            devices.push(format!("/dev/{}", devname.to_string_lossy()));
+        }
+    }
+    devices
+}
+
+fn dump_properties(device: &str) -> Result<Vec<(String, String)>, String> {
+    let raw_info = Command::new("udevadm")
+        .arg("info").arg("-q").arg("property").arg("--export").arg("-n")
+        .arg(device)
+        .output()
+        .map_err(|error| format!("Failed to execute udevadm: {}", error))?;
+
+    if !raw_info.status.success() {
+        return Err(format!("udevadm couldn't find {}", device));
+    }
+
+    lazy_static! {
+        static ref UDEV_REGEX: Regex = Regex::new(r"^(\S+)='(.*)'$")
+            .expect("error parsing regex");
+    }
+
+    let mut properties = Vec::new();
+    for line in raw_info.stdout.lines() {
+        let line = line.map_err(|error| format!("Couldn't split lines from udevadm output: {}", error))?;
+        if let Some(var_value) = UDEV_REGEX.captures(&line) {
+            properties.push((var_value[1].to_string(), var_value[2].to_string()));
+        }
+    }
+
+    Ok(properties)
+}
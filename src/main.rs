@@ -1,4 +1,4 @@
-/// ttynamed - Tool for managing USB serial devices
+//! ttynamed - Tool for managing USB serial devices
 
 extern crate clap;
 use clap::{Arg, App, AppSettings, SubCommand};
@@ -6,123 +6,51 @@ use clap::{Arg, App, AppSettings, SubCommand};
 extern crate directories;
 use directories::ProjectDirs;
 
-use glob::glob;
 #[macro_use]
 extern crate lazy_static;
 
-use regex::{Captures, Regex};
+use regex::Regex;
 use serde::{Serialize, Deserialize};
-use toml;
 
-use std::borrow::Cow;
-use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
-use std::io::{BufRead, Read};
-use std::path::PathBuf;
-use std::process::Command;
-use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
-
-/// Information inherent to the TTY device; notably not including the /dev/ttywhatever
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
-struct Tty {
-    manufacturer: Option<String>,
-    model: Option<String>,
-    serial: Option<String>,
-}
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-/// Include inherent information, and present device handle
-#[derive(Debug, Serialize, Deserialize)]
-struct PresentTty {
-    tty: Tty,
-    device: String,
-}
+mod tty;
+use tty::Tty;
 
-/// Maps from friendly name to Tty instance
-// Using this rather than a raw HashMap, because it might be nice to have program settings here too
-#[derive(Debug, Default, Serialize, Deserialize)]
-struct Configuration {
-    ttys: HashMap<String, Tty>,
-}
+mod backend;
+use backend::{BackendKind, TtyBackend};
 
-/// Converts strings with embedded hex literals like "hello\x20world" to "hello world"
-fn udevadm_decode<'a>(raw: &'a str) -> Cow<'a, str> {
-    lazy_static! {
-        static ref ESC_REGEX: Regex = Regex::new(r"\\x([[:xdigit:]]{2})")
-            .expect("error parsing regex");
-    }
-    ESC_REGEX.replace_all(raw, |caps: &Captures| {
-        match u8::from_str_radix(&caps[1], 16) {
-            Ok(val) => char::from(val),
-            Err(..) => '?',
-        }.to_string() // Replacement character to string
-    })
-}
+mod watch;
 
-fn read_usb_info(dev: &PathBuf) -> Option<PresentTty> {
-    let raw_info = Command::new("udevadm")
-        .arg("info").arg("-q").arg("property").arg("--export").arg("-p")
-        .arg(&dev)
-        .output()
-        .expect("Failed to execute udevadm");
+mod filter;
+use filter::TtyFilter;
 
-    let mut fields = HashMap::<String, String>::new();
+mod info;
 
-    for line in raw_info.stdout.lines() {
-        let line = line.expect("Couldn't split lines from udevadm output!?");
-        lazy_static! {
-            static ref UDEV_REGEX: Regex = Regex::new(r"(\S+)='(\S+)'")
-                .expect("error parsing regex");
-        }
-        if let Some(var_value) = UDEV_REGEX.captures(&line) {
-            fields.insert(var_value[1].to_string(), var_value[2].to_string());
-        }
-    }
+mod format;
 
-    // Ignore anything except USB things
-    if fields.get("ID_BUS") != Some(&String::from("usb")) {
-        return None;
-    }
+mod database;
+use database::Database;
 
-    // if field key in fields has Some value run udevadm_decode() on the value and return result
-    let extract_field = |field: &str| {
-        fields.get(field).map(|raw| udevadm_decode(raw).into_owned())
-    };
+mod driver;
 
-    if let Some(devname) = extract_field("DEVNAME") {
-        Some( PresentTty{
-            tty: Tty {
-                manufacturer: extract_field("ID_VENDOR_ENC"),
-                model:        extract_field("ID_MODEL_ENC"),
-                serial:       extract_field("ID_SERIAL_SHORT"),
-            },
-            device: devname })   
-    } else {
-        None
-    }
-}
-
-// TODO Handle devices where there are multiple dev entries for the same device
-fn available_ttys() -> Vec<PresentTty> {
-    // Generate a list of device handles to inspect - https://stackoverflow.com/a/9914339
-    let mut devs = Vec::new();
-    for candidate in glob("/sys/class/tty/*/device/driver").expect("Failed to read glob pattern") {
-        if let Ok(path) = candidate {
-            // Turn /sys/class/tty/ttyWhatever/device/driver in to /sys/class/tty/ttyWhatever
-            if let Some(devname) = path.ancestors().nth(2) {
-                devs.push(devname.to_path_buf());
-            }
-        }
-    }
+/// Maps from friendly name to Tty instance
+// Using this rather than a raw HashMap, because it might be nice to have program settings here too
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Configuration {
+    ttys: HashMap<String, Tty>,
 
-    let mut ttys = Vec::new();
-    for dev in devs {
-        if let Some(tty) = read_usb_info(&dev) {
-            ttys.push(tty);
-        }
-    }
+    #[serde(default)]
+    filters: HashMap<String, TtyFilter>,
 
-    ttys
+    /// Devices `disable`d via the `disable` subcommand, keyed by friendly name, so `enable` can
+    /// rebind them even though they no longer show up in `backend.available_ttys()`.
+    #[serde(default)]
+    disabled: HashMap<String, driver::DisabledDevice>,
 }
 
 fn load_config(source: &PathBuf) -> Result<Configuration, String> {
@@ -156,8 +84,40 @@ fn save_config(config: Configuration, to: PathBuf) -> Result<(), String> {
     Ok(())
 }
 
-fn pon(raw: &Option<String>) -> String {
-    raw.clone().unwrap_or("None".to_string())
+/// Resolves a friendly name to the /dev path of the single currently-connected device it names.
+fn resolve_device(friendly_name: &str, config: &Configuration, backend: &dyn TtyBackend) -> Result<String, String> {
+    let tty = config.ttys.get(friendly_name)
+        .ok_or_else(|| format!("{} isn't a known friendly name.", friendly_name))?;
+
+    tty.resolve_present_device(friendly_name, backend)?
+        .ok_or_else(|| "That device doesn't appear to be present".to_string())
+}
+
+/// Suggests a friendly name for `tty` from the device database, disambiguating against any
+/// name already used in `config`.
+fn suggest_unused_name(tty: &Tty, database: &Database, config: &Configuration) -> Result<String, String> {
+    let (vendor_id, product_id) = match (tty.vendor_id, tty.product_id) {
+        (Some(vendor_id), Some(product_id)) => (vendor_id, product_id),
+        _ => return Err("Couldn't suggest a name: device wasn't enumerated with a VID/PID; \
+            pass a name explicitly.".to_string()),
+    };
+
+    let base = database.suggest_name(vendor_id, product_id, tty.serial.as_deref())
+        .ok_or_else(|| "Couldn't suggest a name: device isn't in the database; \
+            pass a name explicitly.".to_string())?;
+
+    if !config.ttys.contains_key(&base) {
+        return Ok(base);
+    }
+
+    for suffix in 2.. {
+        let candidate = format!("{}_{}", base, suffix);
+        if !config.ttys.contains_key(&candidate) {
+            return Ok(candidate);
+        }
+    }
+
+    unreachable!()
 }
 
 fn run_app() -> Result<(), String> {
@@ -165,6 +125,12 @@ fn run_app() -> Result<(), String> {
     let subs = vec!(
         SubCommand::with_name("list")
             .about("Shows available TTYs and aliases")
+            .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["table", "json"])
+                .default_value("table")
+                .help("Output format"))
             ,
         SubCommand::with_name("add")
             .about("Add or modify a tty device alias")
@@ -172,8 +138,7 @@ fn run_app() -> Result<(), String> {
                 .help("/dev entry that the device is currently allocated to")
                 .required(true))
             .arg(Arg::with_name("name")
-                .help("Friendly name for the new alias")
-                .required(true))
+                .help("Friendly name for the new alias; if omitted, one is suggested from the device database"))
             // TODO Add optional --hide flag, to hide the TTY in listings
             ,
         SubCommand::with_name("delete")
@@ -181,6 +146,61 @@ fn run_app() -> Result<(), String> {
             .arg(Arg::with_name("name") // TODO add ability to delete based on current device?
                 .help("Friendly name of the device to be deleted")
                 .required(true))
+            ,
+        SubCommand::with_name("watch")
+            .about("Blocks until a named TTY appears, then prints its /dev path")
+            .arg(Arg::with_name("name")
+                .help("Friendly name of the TTY to wait for")
+                .required(true))
+            .arg(Arg::with_name("timeout")
+                .long("timeout")
+                .takes_value(true)
+                .help("Give up and exit with an error after this many seconds"))
+            .arg(Arg::with_name("on-connect")
+                .long("on-connect")
+                .takes_value(true)
+                .help("Command to run each time the device appears; {} is replaced with its /dev path"))
+            ,
+        SubCommand::with_name("match")
+            .about("Tests configured filters against connected devices")
+            ,
+        SubCommand::with_name("filter")
+            .about("Manage filters used by the match subcommand")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name("add")
+                .about("Define or redefine a named filter")
+                .arg(Arg::with_name("name")
+                    .help("Friendly name for the filter")
+                    .required(true))
+                .arg(Arg::with_name("manufacturer")
+                    .long("manufacturer")
+                    .takes_value(true)
+                    .help("Glob pattern to match against a device's manufacturer string"))
+                .arg(Arg::with_name("model")
+                    .long("model")
+                    .takes_value(true)
+                    .help("Glob pattern to match against a device's model string"))
+                .arg(Arg::with_name("serial")
+                    .long("serial")
+                    .takes_value(true)
+                    .help("Glob pattern to match against a device's serial number")))
+            ,
+        SubCommand::with_name("info")
+            .about("Dumps udev properties for one, or all, present TTY devices")
+            .arg(Arg::with_name("device")
+                .help("/dev entry to inspect; if omitted, all present TTYs are dumped"))
+            ,
+        SubCommand::with_name("disable")
+            .about("Unbinds a device's kernel driver, without unplugging it")
+            .arg(Arg::with_name("name")
+                .help("Friendly name of the device to disable")
+                .required(true))
+            ,
+        SubCommand::with_name("enable")
+            .about("Rebinds a device's kernel driver, after it was disabled")
+            .arg(Arg::with_name("name")
+                .help("Friendly name of the device to enable")
+                .required(true))
             );
 
     let subcommand_names: Vec<String> = subs.iter().map(|s| s.get_name().to_string()).collect();
@@ -194,9 +214,25 @@ fn run_app() -> Result<(), String> {
             .help("Friendly name of the TTY"))
         .arg(Arg::with_name("config")
             .help("Config file to use"))
+        .arg(Arg::with_name("backend")
+            .long("backend")
+            .takes_value(true)
+            .possible_values(&["udev", "rusb"])
+            .default_value("udev")
+            .help("USB TTY enumeration backend to use"))
+        .arg(Arg::with_name("database")
+            .long("database")
+            .takes_value(true)
+            .help("Extra VID/PID device database to layer on top of the bundled one"))
         .setting(AppSettings::ArgRequiredElseHelp)
         .get_matches();
 
+    let backend_kind = BackendKind::from_str(arguments.value_of("backend")
+        .expect("'backend' has a default_value, so is always present"))?;
+    let backend = backend::backend(backend_kind);
+
+    let database = Database::load(arguments.value_of("database").map(Path::new))?;
+
     let config_file_path = match arguments.value_of("config") {
         Some(config) => PathBuf::from(config),
         None => match ProjectDirs::from("org", "TTY Named", "ttynamed") {
@@ -208,93 +244,51 @@ fn run_app() -> Result<(), String> {
         }
     };
 
-    let use_colour = true; // TODO make this smarter, and use it to decide whether to pretty-print tables
-
     match arguments.subcommand() {
-        ("list", _) => {
-            const NUM_COLS: usize = 5;
-            let mut rows = Vec::<(termcolor::Color, [String; NUM_COLS])>::new();
+        ("list", Some(list_arguments)) => {
+            let mut rows = Vec::<format::ListRow>::new();
 
             // Render differently depending on whether we have a configuration file
             match load_config(&config_file_path) {
                 Ok(config) => {
                     let mut not_missing = HashSet::new();
 
-                    for present in available_ttys() {
+                    for present in backend.available_ttys() {
                         let mut printed = false;
                         let tty = &present.tty;
 
                         // TODO use caution colour if there are multiple devices that match a known configuration
-                        for known in config.ttys.iter().filter(|k| tty == k.1).map(|k| k.0) {
+                        for known in config.ttys.iter().filter(|k| tty.identity_eq(k.1)).map(|k| k.0) {
                             printed = true;
 
                             not_missing.insert(known);
-                            rows.push((Color::Green, [known.clone(), present.device.clone(),
-                                pon(&tty.manufacturer), pon(&tty.model), pon(&tty.serial)]));
+                            rows.push(format::ListRow::matched(known.clone(), present.device.clone(), tty));
                         }
 
                         if !printed {
-                            let colour = if tty.manufacturer.is_none() ||
-                                            tty.model.is_none() ||
-                                            tty.serial.is_none() {
-                                             Color::Yellow
-                                         } else {
-                                             Color::Black // Sentinel for no colour
-                                         };
-
-                            rows.push((colour, [String::new(), present.device,
-                                pon(&tty.manufacturer), pon(&tty.model), pon(&tty.serial)]));
+                            rows.push(format::ListRow::unmatched(present.device.clone(), tty, &database));
                         }
                     }
 
                     // Also, display the TTY hardware we know about, but that isn't connected
                     for known in config.ttys.iter().filter(|k| !not_missing.contains(k.0)) {
-                        rows.push((Color::Red, [known.0.clone(), "(missing)".to_string(),
-                            pon(&known.1.manufacturer), pon(&known.1.model), pon(&known.1.serial)]));
+                        rows.push(format::ListRow::missing(known.0.clone(), known.1));
                     }
                 },
 
                 // Config file wasn't successfully loaded; just list what we know we've got
                 Err(error) => {
                     eprintln!("{}", error);
-                    println!("");
-                    for present in available_ttys() {
-                        let tty = present.tty;
-                        rows.push((Color::Black, [present.device, pon(&tty.manufacturer),
-                            pon(&tty.model), pon(&tty.serial), String::new()]));
+                    println!();
+                    for present in backend.available_ttys() {
+                        rows.push(format::ListRow::unmatched(present.device.clone(), &present.tty, &database));
                     }
                 }
             };
 
-            let mut widths = [0usize; NUM_COLS];
-            for row in &rows {
-                for (column_index, field) in row.1.iter().enumerate() {
-                    widths[column_index] = cmp::max(field.len(), widths[column_index]);
-                }
-            }
-
-            let mut stdout = StandardStream::stdout(ColorChoice::Always);
-            for row in &rows {
-                if use_colour {
-                    let colour = row.0;
-                    if colour == Color::Black {
-                        stdout.set_color(&ColorSpec::new()).expect("Colour change failed");
-                    } else {
-                        stdout.set_color(ColorSpec::new().set_fg(Some(colour)))
-                            .expect("Colour change failed");
-                    }
-                }
-
-                for (column_index, field) in row.1.iter().enumerate() {
-                    if widths[column_index] > 0 {
-                        print!("{:1$}", field, widths[column_index] + 2);
-                    }
-                }
-                println!("");
-            }
-
-            if rows.is_empty() {
-                println!("No USB TTYs present.");
+            match list_arguments.value_of("format") {
+                Some("json") => format::print_json(&rows)?,
+                _ => format::print_table(&rows),
             }
         },
 
@@ -314,26 +308,81 @@ fn run_app() -> Result<(), String> {
             }
         },
 
-        ("add", Some(add_arguments)) => {
-            let friendly = add_arguments.value_of("name")
-                .expect("'name' argument is required, but missing").to_string();
+        ("watch", Some(watch_arguments)) => {
+            let friendly_name = watch_arguments.value_of("name")
+                .expect("'name' argument is required, but missing");
 
-            if subcommand_names.contains(&friendly) {
-                return Err(format!("Invalid friendly name; '{}' is a subcommand.", friendly));
-            }
+            let timeout = match watch_arguments.value_of("timeout") {
+                Some(raw) => Some(Duration::from_secs(raw.parse::<u64>()
+                    .map_err(|_| "Invalid --timeout value; expected a whole number of seconds".to_string())?)),
+                None => None,
+            };
 
-            if Regex::new(r"[^a-zA-Z0-9_--]").unwrap().is_match(&friendly) {
-                return Err(format!("Friendly names must only contain letters, digits, _, and -"));
-            }
+            let on_connect = watch_arguments.value_of("on-connect");
+
+            let config = load_config(&config_file_path)?;
+
+            watch::run(friendly_name, &config, backend.as_ref(), timeout, on_connect)?;
+        },
+
+        ("match", _) => {
+            let config = load_config(&config_file_path)?;
+            filter::run(&config.filters, backend.as_ref())?;
+        },
+
+        ("filter", Some(filter_arguments)) => match filter_arguments.subcommand() {
+            ("add", Some(add_arguments)) => {
+                let name = add_arguments.value_of("name")
+                    .expect("'name' argument is required, but missing");
+
+                let mut config = load_config(&config_file_path)?;
+
+                filter::add(
+                    name,
+                    add_arguments.value_of("manufacturer"),
+                    add_arguments.value_of("model"),
+                    add_arguments.value_of("serial"),
+                    &mut config,
+                )?;
+
+                save_config(config, config_file_path)?;
+
+                println!("{} was added successfully!", name);
+            },
+            _ => unreachable!(),
+        },
+
+        ("info", Some(info_arguments)) => {
+            info::run(info_arguments.value_of("device"))?;
+        },
+
+        ("disable", Some(disable_arguments)) => {
+            let friendly_name = disable_arguments.value_of("name")
+                .expect("'name' argument is required, but missing");
 
             let mut config = load_config(&config_file_path)?;
+            driver::disable(friendly_name, &mut config, backend.as_ref())?;
+            save_config(config, config_file_path)?;
+        },
+
+        ("enable", Some(enable_arguments)) => {
+            let friendly_name = enable_arguments.value_of("name")
+                .expect("'name' argument is required, but missing");
+
+            let mut config = load_config(&config_file_path)?;
+            driver::enable(friendly_name, &mut config)?;
+            save_config(config, config_file_path)?;
+        },
+
+        ("add", Some(add_arguments)) => {
+            let mut config = load_config(&config_file_path)?;
 
             let device = add_arguments.value_of("device")
                 .expect("'device' argument is required, but missing");
 
             // Get information on the device to be added
             let mut to_add = None;
-            for tty in available_ttys() {
+            for tty in backend.available_ttys() {
                 if tty.device == device {
                     if to_add.is_none() {
                         to_add = Some(tty);
@@ -347,10 +396,23 @@ fn run_app() -> Result<(), String> {
             }
             let to_add = to_add.unwrap();
 
+            let friendly = match add_arguments.value_of("name") {
+                Some(name) => name.to_string(),
+                None => suggest_unused_name(&to_add.tty, &database, &config)?,
+            };
+
+            if subcommand_names.contains(&friendly) {
+                return Err(format!("Invalid friendly name; '{}' is a subcommand.", friendly));
+            }
+
+            if Regex::new(r"[^a-zA-Z0-9_--]").unwrap().is_match(&friendly) {
+                return Err("Friendly names must only contain letters, digits, _, and -".to_string());
+            }
+
             // Remove any matching entries in the config; we're effectively modifying, not adding
             let mut to_remove = Vec::new();
             for (name, tty) in &config.ttys {
-                if tty == &to_add.tty {
+                if tty.identity_eq(&to_add.tty) {
                     to_remove.push(name.clone());
                 }
             }
@@ -374,32 +436,7 @@ fn run_app() -> Result<(), String> {
             if let Some(friendly_name) = arguments.value_of("name") {
                 let config = load_config(&config_file_path)?;
 
-                let tty = match config.ttys.get(friendly_name) {
-                    Some(tty) => tty,
-                    None => {
-                        return Err(format!("{} isn't a known friendly name.", friendly_name));
-                    }
-                };
-
-                let mut pick = None;
-                for candidate in available_ttys() {
-                    if &candidate.tty == tty {
-                        if pick.is_none() {
-                            pick = Some(candidate.device);
-                        } else {
-                            return Err(format!("Multiple devices could be {}", friendly_name))
-                        }
-                    }
-                }
-
-                return match pick {
-                    Some(pick) => {
-                        println!("{}", pick);
-                        Ok(())
-                    },
-                    None => Err(format!("That device doesn't appear to be present"))
-                }
-
+                println!("{}", resolve_device(friendly_name, &config, backend.as_ref())?);
             } else {
                 unreachable!(); // No subcommand nor friendly_name
             }
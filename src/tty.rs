@@ -0,0 +1,64 @@
+//! Core data types describing a USB TTY, independent of how it was discovered.
+
+use serde::{Serialize, Deserialize};
+
+use crate::backend::TtyBackend;
+
+/// Information inherent to the TTY device; notably not including the /dev/ttywhatever
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Tty {
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial: Option<String>,
+
+    /// Numeric USB vendor ID, when the enumeration backend exposed one
+    #[serde(default)]
+    pub vendor_id: Option<u16>,
+
+    /// Numeric USB product ID, when the enumeration backend exposed one
+    #[serde(default)]
+    pub product_id: Option<u16>,
+}
+
+impl Tty {
+    /// Compares two `Tty`s the way friendly-name resolution always has: by manufacturer, model
+    /// and serial only. vendor_id/product_id are deliberately excluded, since configs written
+    /// before those fields existed deserialize them as `None` and would otherwise stop matching
+    /// the `Some(..)` a backend reports for the same, still-connected device.
+    pub fn identity_eq(&self, other: &Tty) -> bool {
+        self.manufacturer == other.manufacturer
+            && self.model == other.model
+            && self.serial == other.serial
+    }
+
+    /// Scans `backend`'s present devices for ones matching this `Tty`'s identity, the same way
+    /// every friendly-name lookup (`resolve_device`, `watch`, `disable`) needs to: `Ok(None)` if
+    /// none match, `Ok(Some(device))` if exactly one does, and an error if more than one does.
+    /// Ambiguity is a real risk here, since `identity_eq` deliberately ignores vendor_id/
+    /// product_id and plenty of boards have no serial number at all.
+    pub fn resolve_present_device(&self, friendly_name: &str, backend: &dyn TtyBackend) -> Result<Option<String>, String> {
+        let mut pick = None;
+        for candidate in backend.available_ttys() {
+            if candidate.tty.identity_eq(self) {
+                if pick.is_none() {
+                    pick = Some(candidate.device);
+                } else {
+                    return Err(format!("Multiple devices could be {}", friendly_name));
+                }
+            }
+        }
+
+        Ok(pick)
+    }
+}
+
+/// Include inherent information, and present device handle
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PresentTty {
+    pub tty: Tty,
+    pub device: String,
+}
+
+pub fn pon(raw: &Option<String>) -> String {
+    raw.clone().unwrap_or("None".to_string())
+}
@@ -0,0 +1,143 @@
+//! `watch` subcommand: blocks until a named TTY appears (as xremap's device watcher does),
+//! optionally running a command each time it does.
+
+use std::io;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use inotify::{Inotify, WatchMask};
+
+use crate::backend::TtyBackend;
+use crate::tty::Tty;
+use crate::Configuration;
+
+/// The sysfs attributes for a just-created /dev node can lag slightly behind the IN_CREATE
+/// event, so retry the enumeration a few times with a short backoff before giving up on a match.
+const MATCH_ATTEMPTS: u32 = 5;
+const MATCH_BACKOFF: Duration = Duration::from_millis(100);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn run(
+    name: &str,
+    config: &Configuration,
+    backend: &dyn TtyBackend,
+    timeout: Option<Duration>,
+    on_connect: Option<&str>,
+) -> Result<(), String> {
+    let tty = config.ttys.get(name)
+        .ok_or_else(|| format!("{} isn't a known friendly name.", name))?;
+
+    let deadline = timeout.map(|t| Instant::now() + t);
+
+    loop {
+        let device = wait_for_match(name, tty, backend, deadline)?;
+        println!("{}", device);
+
+        match on_connect {
+            Some(cmd) => {
+                run_on_connect(cmd, &device);
+                wait_for_absence(name, tty, backend, deadline)?;
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Blocks until `tty` shows up in `backend`'s enumeration, returning its /dev path.
+fn wait_for_match(name: &str, tty: &Tty, backend: &dyn TtyBackend, deadline: Option<Instant>) -> Result<String, String> {
+    if let Some(device) = find_match(name, tty, backend)? {
+        return Ok(device);
+    }
+
+    let mut inotify = Inotify::init()
+        .map_err(|error| format!("Failed to initialize inotify: {}", error))?;
+    inotify.add_watch("/dev", WatchMask::CREATE | WatchMask::DELETE)
+        .map_err(|error| format!("Failed to watch /dev: {}", error))?;
+
+    let mut buffer = [0u8; 4096];
+    loop {
+        if let Some(deadline) = deadline {
+            if Instant::now() >= deadline {
+                return Err("Timed out waiting for the device to appear.".to_string());
+            }
+        }
+
+        match inotify.read_events(&mut buffer) {
+            Ok(events) => {
+                if events.count() > 0 {
+                    if let Some(device) = find_match_with_retry(name, tty, backend)? {
+                        return Ok(device);
+                    }
+                }
+            }
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => {}
+            Err(error) => return Err(format!("Error reading inotify events: {}", error)),
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Blocks until `tty` no longer shows up in `backend`'s enumeration.
+fn wait_for_absence(name: &str, tty: &Tty, backend: &dyn TtyBackend, deadline: Option<Instant>) -> Result<(), String> {
+    let mut inotify = Inotify::init()
+        .map_err(|error| format!("Failed to initialize inotify: {}", error))?;
+    inotify.add_watch("/dev", WatchMask::CREATE | WatchMask::DELETE)
+        .map_err(|error| format!("Failed to watch /dev: {}", error))?;
+
+    let mut buffer = [0u8; 4096];
+    loop {
+        if find_match(name, tty, backend)?.is_none() {
+            return Ok(());
+        }
+
+        if let Some(deadline) = deadline {
+            if Instant::now() >= deadline {
+                return Err("Timed out waiting for the device to go away.".to_string());
+            }
+        }
+
+        match inotify.read_events(&mut buffer) {
+            Ok(_) => {},
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => {}
+            Err(error) => return Err(format!("Error reading inotify events: {}", error)),
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Looks for the single present device matching `tty`, erroring out (rather than silently
+/// picking one) if more than one does — the same ambiguity check `resolve_device` applies.
+fn find_match(name: &str, tty: &Tty, backend: &dyn TtyBackend) -> Result<Option<String>, String> {
+    tty.resolve_present_device(name, backend)
+}
+
+fn find_match_with_retry(name: &str, tty: &Tty, backend: &dyn TtyBackend) -> Result<Option<String>, String> {
+    for attempt in 0..MATCH_ATTEMPTS {
+        if let Some(device) = find_match(name, tty, backend)? {
+            return Ok(Some(device));
+        }
+        if attempt + 1 < MATCH_ATTEMPTS {
+            thread::sleep(MATCH_BACKOFF);
+        }
+    }
+    Ok(None)
+}
+
+/// Runs `cmd` with any `{}` replaced by the resolved device path, as --on-connect's handler.
+fn run_on_connect(cmd: &str, device: &str) {
+    let resolved = cmd.replace("{}", device);
+
+    match Command::new("sh").arg("-c").arg(&resolved).status() {
+        Ok(status) if !status.success() => {
+            eprintln!("--on-connect command exited with {}", status);
+        }
+        Ok(_) => {}
+        Err(error) => {
+            eprintln!("Failed to run --on-connect command '{}': {}", resolved, error);
+        }
+    }
+}